@@ -7,14 +7,47 @@ struct PriorityItem<R, T: PriorityValue> {
   pub priority: T,
 }
 
+#[derive(Clone, Copy)]
 pub enum PriorityMode {
   MinimizeHead,
   MaximizeHead
 }
 
+impl PriorityMode {
+  fn inverted(self) -> Self {
+    match self {
+      PriorityMode::MinimizeHead => PriorityMode::MaximizeHead,
+      PriorityMode::MaximizeHead => PriorityMode::MinimizeHead,
+    }
+  }
+}
+
+/** An opaque, stable handle to an item inserted into a PriorityQueue, usable with `update_priority` and `remove` regardless of how the item has moved around the underlying heap */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Handle(usize);
+
+impl Handle {
+  /** A handle that never refers to anything in any queue, returned by `insert` when the item is discarded immediately instead of being stored */
+  fn invalid() -> Self {
+    Handle(usize::MAX)
+  }
+}
+
 pub struct PriorityQueue<R, T: PriorityValue> {
   data: Vec<PriorityItem<R, T>>,
+  // The mode the queue was constructed with: what `beats` and `into_sorted_vec`'s output order are defined in terms of
   mode: PriorityMode,
+  // The orientation the underlying heap is actually stored in. For an unbounded queue this is always `mode`, so the
+  // root is the best item. For a bounded queue it is the inverse of `mode`, so the root is the *worst* retained item,
+  // letting `insert` reject or evict in O(log max_size) by comparing against the root instead of scanning the leaves
+  heap_mode: PriorityMode,
+  max_size: Option<usize>,
+  // index_to_handle[i] is the handle currently occupying data[i]; handle_to_index[h] is the
+  // current index of handle h, or None if that handle's item is no longer in the queue
+  index_to_handle: Vec<usize>,
+  handle_to_index: Vec<Option<usize>>,
+  // Handles freed by eviction/remove/take, recycled by alloc_handle instead of growing handle_to_index forever
+  free_handles: Vec<usize>,
 }
 
 impl <R, T: PriorityValue>PriorityQueue<R, T> {
@@ -23,10 +56,80 @@ impl <R, T: PriorityValue>PriorityQueue<R, T> {
   pub fn new(size_hint: usize, mode: PriorityMode) -> Self {
     Self {
       data: Vec::with_capacity(size_hint),
-      mode: mode,
+      heap_mode: mode,
+      mode,
+      max_size: None,
+      index_to_handle: Vec::with_capacity(size_hint),
+      handle_to_index: Vec::with_capacity(size_hint),
+      free_handles: Vec::new(),
     }
   }
 
+  /** Construct a bounded priority queue that retains only the `max_size` most extreme items (per `mode`) seen across all `insert` calls, discarding the rest in O(log max_size) per insert. Because the retained set is kept as a heap in the *inverse* of `mode` (so the weakest survivor is always at the root), `peek`/`take` on a bounded queue surface the weakest retained item first; call `into_sorted_vec` to get the retained items back in `mode` order */
+  pub fn with_capacity_limit(max_size: usize, mode: PriorityMode) -> Self {
+    Self {
+      data: Vec::with_capacity(max_size),
+      heap_mode: mode.inverted(),
+      mode,
+      max_size: Some(max_size),
+      index_to_handle: Vec::with_capacity(max_size),
+      handle_to_index: Vec::with_capacity(max_size),
+      free_handles: Vec::new(),
+    }
+  }
+
+  /** Construct a priority queue from a vector of (item, priority) pairs in O(n) using a bottom-up heapify instead of N individual inserts */
+  pub fn from_vec(items: Vec<(R, T)>, mode: PriorityMode) -> Self {
+    let data: Vec<PriorityItem<R, T>> = items.into_iter().map(|(item, priority)| PriorityItem { item, priority }).collect();
+    let index_to_handle: Vec<usize> = (0..data.len()).collect();
+    let handle_to_index: Vec<Option<usize>> = (0..data.len()).map(Some).collect();
+    let mut queue = Self { data, heap_mode: mode, mode, max_size: None, index_to_handle, handle_to_index, free_handles: Vec::new() };
+
+    // Only the nodes with children can violate the heap property, so start at the last parent
+    // and sift each one down, working back towards the root
+    if queue.data.len() > 1 {
+      let mut idx = queue.data.len() / 2 - 1;
+
+      loop {
+        queue.sift_down(idx);
+
+        if idx == 0 {
+          break;
+        }
+
+        idx -= 1;
+      }
+    }
+
+    queue
+  }
+
+  /** Allocate a currently-unassigned handle, recycling one freed by a previous eviction/remove/take if one is available so the handle table stays bounded by the number of handles live at once rather than the number of inserts ever made */
+  fn alloc_handle(&mut self) -> Handle {
+    match self.free_handles.pop() {
+      Some(handle) => Handle(handle),
+      None => {
+        let handle = self.handle_to_index.len();
+        self.handle_to_index.push(None);
+        Handle(handle)
+      }
+    }
+  }
+
+  /** Invalidate a handle and return its slot to the free list for reuse */
+  fn free_handle(&mut self, handle: usize) {
+    self.handle_to_index[handle] = None;
+    self.free_handles.push(handle);
+  }
+
+  /** Swap two positions in the heap, keeping the handle lookup tables in sync */
+  fn swap(&mut self, a: usize, b: usize) {
+    self.data.swap(a, b);
+    self.index_to_handle.swap(a, b);
+    self.handle_to_index[self.index_to_handle[a]] = Some(a);
+    self.handle_to_index[self.index_to_handle[b]] = Some(b);
+  }
+
   /** The parent of a node is at index - 1 / 2, we're relying on integer division flooring */
   fn parent(&self, idx: usize) -> usize {
     (idx - 1) / 2
@@ -65,31 +168,157 @@ impl <R, T: PriorityValue>PriorityQueue<R, T> {
 
   /** Returns true if the current values for parent and child violate the heap property */
   fn violates_heap_property(&self, parent: usize, child: usize) -> bool {
-    match &self.mode {
+    match &self.heap_mode {
       PriorityMode::MinimizeHead => self.data[parent].priority > self.data[child].priority,
       PriorityMode::MaximizeHead => self.data[parent].priority < self.data[child].priority,
     }
   }
 
-  /** Insert a new item into the priority queue */
-  pub fn insert(&mut self, item: R, priority: T) {
-    // First insert at the end
-    self.data.push(PriorityItem { item, priority });
+  /** Returns true if `a` is the more extreme of the two priorities for this queue's mode (i.e. would belong closer to the `mode`-defined head) */
+  fn beats(&self, a: &T, b: &T) -> bool {
+    match &self.mode {
+      PriorityMode::MinimizeHead => a < b,
+      PriorityMode::MaximizeHead => a > b,
+    }
+  }
+
+  /** Returns true if `a` is more extreme than `b` under the heap's actual storage orientation, i.e. whether a node holding `a` instead of `b` would need to move towards the root */
+  fn heap_beats(&self, a: &T, b: &T) -> bool {
+    match &self.heap_mode {
+      PriorityMode::MinimizeHead => a < b,
+      PriorityMode::MaximizeHead => a > b,
+    }
+  }
+
+  /** Overwrite the root with a new item and sift down once, returning the new item's handle and the displaced item. The displaced item's handle is freed and immediately reissued to the new item, so the returned handle equals the displaced item's old one */
+  fn replace_root(&mut self, new_item: PriorityItem<R, T>) -> (Handle, R) {
+    self.free_handle(self.index_to_handle[0]);
+
+    let handle = self.alloc_handle();
+    self.index_to_handle[0] = handle.0;
+    self.handle_to_index[handle.0] = Some(0);
 
-    // Next work up the tree swapping this value with it's parent if it violates the heap property
-    let mut idx = self.data.len() - 1;
+    let old = std::mem::replace(&mut self.data[0], new_item);
+    self.sift_down(0);
+    (handle, old.item)
+  }
 
+  /** Starting from idx, work up the tree swapping this node with its parent while it violates the heap property. Returns the node's final resting index */
+  fn sift_up(&mut self, mut idx: usize) -> usize {
     while idx != 0 {
       let parent = self.parent(idx);
       if self.violates_heap_property(parent, idx) {
         // This parent child relationship violates the heap property
         // swap them and then make sure that the heap property is not violated at depth - 1 by repeating
-        self.data.swap(parent, idx);
+        self.swap(parent, idx);
         idx = parent;
       } else {
         break; // We are done since the heap property is maintained
       }
     }
+
+    idx
+  }
+
+  /** Insert a new item into the priority queue, returning a stable handle that can later be passed to `update_priority` or `remove`. If the queue is bounded and full, the new item is discarded unless it beats the worst retained item (found in O(1), since a bounded queue's heap is kept in the inverse of `mode` so the weakest survivor is always the root), in which case it evicts it in O(log max_size); in either case of discarding, the returned handle refers to nothing and further operations on it are no-ops */
+  pub fn insert(&mut self, item: R, priority: T) -> Handle {
+    if let Some(limit) = self.max_size {
+      if limit == 0 {
+        return Handle::invalid();
+      }
+
+      if self.data.len() >= limit {
+        // Drop the newcomer if it is no better than the worst item we're already retaining
+        if !self.beats(&priority, &self.data[0].priority) {
+          return Handle::invalid();
+        }
+
+        // Otherwise evict the worst retained item (always the root, since the heap is inverted) and
+        // restore the heap property with a single sift-down
+        return self.replace_root(PriorityItem { item, priority }).0;
+      }
+    }
+
+    let handle = self.alloc_handle();
+
+    // First insert at the end
+    self.data.push(PriorityItem { item, priority });
+    self.index_to_handle.push(handle.0);
+    let idx = self.data.len() - 1;
+    self.handle_to_index[handle.0] = Some(idx);
+
+    // Next work up the tree swapping this value with its parent if it violates the heap property
+    self.sift_up(idx);
+
+    handle
+  }
+
+  /** Replace the head of the priority queue with a new item in a single O(log n) sift-down, returning the old head. If the queue is empty this just inserts the item and returns None */
+  pub fn replace(&mut self, item: R, priority: T) -> Option<R> {
+    if self.data.is_empty() {
+      self.insert(item, priority);
+      return None;
+    }
+
+    Some(self.replace_root(PriorityItem { item, priority }).1)
+  }
+
+  /** If the new item beats the current head, swap it in for the head with a single O(log n) sift-down and return the old head. If the new item would not displace the current head (or the queue is empty) it is returned immediately without being stored */
+  pub fn push_pop(&mut self, item: R, priority: T) -> R {
+    if self.data.is_empty() || !self.beats(&priority, &self.data[0].priority) {
+      return item;
+    }
+
+    self.replace_root(PriorityItem { item, priority }).1
+  }
+
+  /** Starting from idx, work down the tree swapping any parent that violates the heap property with its best child, until the heap property is restored */
+  fn sift_down(&mut self, mut idx: usize) {
+    // Find the best candidate for swapping (in a minimize heap the lowest value child, in a maximize heap the highest value child)
+    while let Some(child) = self.best_child(idx) {
+      // If it violates the heap property then swap it out
+      if self.violates_heap_property(idx, child) {
+        self.swap(idx, child);
+        idx = child;
+      } else {
+        break;
+      }
+    }
+  }
+
+  /** Look at the highest priority item in the priority queue without removing it */
+  pub fn peek(&self) -> Option<&R> {
+    self.data.first().map(|x| &x.item)
+  }
+
+  /** The number of items currently in the priority queue */
+  pub fn len(&self) -> usize {
+    self.data.len()
+  }
+
+  /** True if the priority queue has no items in it */
+  pub fn is_empty(&self) -> bool {
+    self.data.is_empty()
+  }
+
+  /** The number of items the priority queue can hold before it needs to reallocate */
+  pub fn capacity(&self) -> usize {
+    self.data.capacity()
+  }
+
+  /** Reserve capacity for at least `additional` more items */
+  pub fn reserve(&mut self, additional: usize) {
+    self.data.reserve(additional);
+    self.index_to_handle.reserve(additional);
+    self.handle_to_index.reserve(additional);
+  }
+
+  /** Remove all items from the priority queue. Previously issued handles no longer refer to anything, but handle numbering restarts from zero, so a later insert can reissue a handle equal to an old one */
+  pub fn clear(&mut self) {
+    self.data.clear();
+    self.index_to_handle.clear();
+    self.handle_to_index.clear();
+    self.free_handles.clear();
   }
 
   /** Take the highest priority item from the priority queue */
@@ -104,35 +333,333 @@ impl <R, T: PriorityValue>PriorityQueue<R, T> {
     }
 
     // First swap the item we want to remove with the last item in the heap
-    self.data.swap(0, heap_len - 1);
+    self.swap(0, heap_len - 1);
+
+    // That item's handle no longer refers to anything still in the queue
+    self.free_handle(self.index_to_handle[heap_len - 1]);
+    self.index_to_handle.pop();
 
     // Next, remove the item we want from the heap by popping
     let result_value = self.data.pop();
 
     // Now starting from the first node in the tree work down until the heap property is restored
-    // by swapping any parent that violates the heap property with one of it's children
-    let mut idx = 0;
+    self.sift_down(0);
 
-    while idx < heap_len {
+    // Finally return our removed heap item
+    result_value.map(|x| x.item)
+  }
 
-      // Find the best candidate for swapping (in a minimize heap the highest value child, in a maximize heap the lowest value child)
-      if let Some(child) = self.best_child(idx) {
+  /** Change the priority of the item referred to by `handle` and restore the heap property in O(log n), returning false if the handle no longer refers to anything in the queue. This lets Dijkstra-style callers relax edges in place instead of inserting duplicate entries */
+  pub fn update_priority(&mut self, handle: Handle, new_priority: T) -> bool {
+    let idx = match self.handle_to_index.get(handle.0).copied().flatten() {
+      Some(idx) => idx,
+      None => return false,
+    };
 
-        // If it violates the heap property then swap it out
-        if self.violates_heap_property(idx, child) {
-          self.data.swap(idx, child);
-          idx = child;
-        } else {
-          break;
+    // If the new priority is more extreme under the heap's actual storage orientation than the old
+    // one, the item can only need to move towards the root, otherwise it can only need to move
+    // towards the leaves
+    let improved = self.heap_beats(&new_priority, &self.data[idx].priority);
+    self.data[idx].priority = new_priority;
+
+    if improved {
+      self.sift_up(idx);
+    } else {
+      self.sift_down(idx);
+    }
+
+    true
+  }
+
+  /** Remove the item referred to by `handle` from the queue in O(log n), returning None if the handle no longer refers to anything in the queue */
+  pub fn remove(&mut self, handle: Handle) -> Option<R> {
+    let idx = self.handle_to_index.get(handle.0).copied().flatten()?;
+
+    let last = self.data.len() - 1;
+    self.swap(idx, last);
+
+    self.free_handle(self.index_to_handle[last]);
+    self.index_to_handle.pop();
+    let result = self.data.pop();
+
+    // The item that took idx's place came from the last, unrelated slot, so it could violate the
+    // heap property in either direction. Sifting up is a no-op unless it actually moves, so only
+    // sift down if it stayed put
+    if idx < self.data.len() && self.sift_up(idx) == idx {
+      self.sift_down(idx);
+    }
+
+    result.map(|x| x.item)
+  }
+
+  /** Consume the priority queue, repeatedly taking from it to produce a fully ordered vector (ascending for MinimizeHead, descending for MaximizeHead) */
+  pub fn into_sorted_vec(mut self) -> Vec<R> {
+    let mut result = Vec::with_capacity(self.data.len());
+    let bounded = self.max_size.is_some();
+
+    while let Some(item) = self.take() {
+      result.push(item);
+    }
+
+    // A bounded queue's heap is stored in the inverse of `mode`, so `take` surfaces the weakest
+    // retained item first; reverse to get back to `mode` order
+    if bounded {
+      result.reverse();
+    }
+
+    result
+  }
+}
+
+/** A priority queue that exposes both extremes at once, backed by a min-max heap stored in a flat Vec. Levels alternate between min-levels and max-levels (level = floor(log2(idx+1))): nodes on min-levels are smaller than all of their descendants, nodes on max-levels are larger than all of theirs */
+pub struct DoubleEndedPriorityQueue<R, T: PriorityValue> {
+  data: Vec<PriorityItem<R, T>>,
+}
+
+impl <R, T: PriorityValue>DoubleEndedPriorityQueue<R, T> {
+
+  /** Construct a new double-ended priority queue with size_hint preallocated space */
+  pub fn new(size_hint: usize) -> Self {
+    Self {
+      data: Vec::with_capacity(size_hint),
+    }
+  }
+
+  /** The number of items currently in the queue */
+  pub fn len(&self) -> usize {
+    self.data.len()
+  }
+
+  /** True if the queue has no items in it */
+  pub fn is_empty(&self) -> bool {
+    self.data.is_empty()
+  }
+
+  /** The parent of a node is at index - 1 / 2, we're relying on integer division flooring */
+  fn parent(&self, idx: usize) -> usize {
+    (idx - 1) / 2
+  }
+
+  /** The child of a node is at (idx * 2) + 1 */
+  fn children(&self, idx: usize) -> (usize, usize) {
+    ((idx * 2) + 1, (idx * 2) + 2)
+  }
+
+  /** The grandparent of a node, if it has one */
+  fn grandparent(&self, idx: usize) -> Option<usize> {
+    if idx == 0 {
+      return None;
+    }
+
+    let parent = self.parent(idx);
+
+    if parent == 0 {
+      None
+    } else {
+      Some(self.parent(parent))
+    }
+  }
+
+  /** True if idx is on a min-level (levels are numbered from the root, which is level 0 and a min-level) */
+  fn is_min_level(&self, idx: usize) -> bool {
+    (idx + 1).ilog2().is_multiple_of(2)
+  }
+
+  /** The children and grandchildren of idx that currently exist, tagged with whether each one is a grandchild */
+  fn descendants(&self, idx: usize) -> [Option<(usize, bool)>; 6] {
+    let mut result = [None; 6];
+    let mut n = 0;
+    let len = self.data.len();
+    let (child1, child2) = self.children(idx);
+
+    for child in [child1, child2] {
+      if child < len {
+        result[n] = Some((child, false));
+        n += 1;
+
+        let (grandchild1, grandchild2) = self.children(child);
+        for grandchild in [grandchild1, grandchild2] {
+          if grandchild < len {
+            result[n] = Some((grandchild, true));
+            n += 1;
+          }
         }
+      }
+    }
+
+    result
+  }
+
+  /** The descendant (child or grandchild) of idx with the smallest priority, if any exist */
+  fn smallest_descendant(&self, idx: usize) -> Option<(usize, bool)> {
+    self.descendants(idx).into_iter().flatten().min_by(|a, b| self.data[a.0].priority.cmp(&self.data[b.0].priority))
+  }
+
+  /** The descendant (child or grandchild) of idx with the largest priority, if any exist */
+  fn largest_descendant(&self, idx: usize) -> Option<(usize, bool)> {
+    self.descendants(idx).into_iter().flatten().max_by(|a, b| self.data[a.0].priority.cmp(&self.data[b.0].priority))
+  }
 
+  /** Sift idx up among nodes smaller than it, skipping a level at a time to compare against grandparents */
+  fn push_up_min(&mut self, mut idx: usize) {
+    while let Some(grandparent) = self.grandparent(idx) {
+      if self.data[idx].priority < self.data[grandparent].priority {
+        self.data.swap(idx, grandparent);
+        idx = grandparent;
       } else {
         break;
       }
     }
+  }
 
-    // Finally return our removed heap item
-    result_value.map(|x| x.item)
+  /** Sift idx up among nodes larger than it, skipping a level at a time to compare against grandparents */
+  fn push_up_max(&mut self, mut idx: usize) {
+    while let Some(grandparent) = self.grandparent(idx) {
+      if self.data[idx].priority > self.data[grandparent].priority {
+        self.data.swap(idx, grandparent);
+        idx = grandparent;
+      } else {
+        break;
+      }
+    }
+  }
+
+  /** Restore the min-max heap property after inserting at idx */
+  fn push_up(&mut self, idx: usize) {
+    if idx == 0 {
+      return;
+    }
+
+    let parent = self.parent(idx);
+
+    if self.is_min_level(idx) {
+      if self.data[idx].priority > self.data[parent].priority {
+        // idx belongs on a max-oriented path instead, swap down into the parent's slot and carry on from there
+        self.data.swap(idx, parent);
+        self.push_up_max(parent);
+      } else {
+        self.push_up_min(idx);
+      }
+    } else {
+      if self.data[idx].priority < self.data[parent].priority {
+        self.data.swap(idx, parent);
+        self.push_up_min(parent);
+      } else {
+        self.push_up_max(idx);
+      }
+    }
+  }
+
+  /** Insert a new item into the double-ended priority queue */
+  pub fn insert(&mut self, item: R, priority: T) {
+    self.data.push(PriorityItem { item, priority });
+    let idx = self.data.len() - 1;
+    self.push_up(idx);
+  }
+
+  /** Look at the smallest item in the queue without removing it */
+  pub fn peek_min(&self) -> Option<&R> {
+    self.data.first().map(|x| &x.item)
+  }
+
+  /** Look at the largest item in the queue without removing it */
+  pub fn peek_max(&self) -> Option<&R> {
+    match self.data.len() {
+      0 => None,
+      1 => Some(&self.data[0].item),
+      2 => Some(&self.data[1].item),
+      _ => {
+        if self.data[1].priority > self.data[2].priority {
+          Some(&self.data[1].item)
+        } else {
+          Some(&self.data[2].item)
+        }
+      }
+    }
+  }
+
+  /** Restore the min-max heap property downwards from idx, treating idx as a min-level node */
+  fn trickle_down_min(&mut self, mut idx: usize) {
+    while let Some((descendant, is_grandchild)) = self.smallest_descendant(idx) {
+      if self.data[descendant].priority >= self.data[idx].priority {
+        break;
+      }
+
+      self.data.swap(descendant, idx);
+
+      if !is_grandchild {
+        break;
+      }
+
+      // The item from idx has moved down into the grandchild's slot, so check it isn't now bigger than its new parent
+      let parent = self.parent(descendant);
+      if self.data[descendant].priority > self.data[parent].priority {
+        self.data.swap(descendant, parent);
+      }
+
+      idx = descendant;
+    }
+  }
+
+  /** Restore the min-max heap property downwards from idx, treating idx as a max-level node */
+  fn trickle_down_max(&mut self, mut idx: usize) {
+    while let Some((descendant, is_grandchild)) = self.largest_descendant(idx) {
+      if self.data[descendant].priority <= self.data[idx].priority {
+        break;
+      }
+
+      self.data.swap(descendant, idx);
+
+      if !is_grandchild {
+        break;
+      }
+
+      // The item from idx has moved down into the grandchild's slot, so check it isn't now smaller than its new parent
+      let parent = self.parent(descendant);
+      if self.data[descendant].priority < self.data[parent].priority {
+        self.data.swap(descendant, parent);
+      }
+
+      idx = descendant;
+    }
+  }
+
+  /** Remove and return the smallest item in the queue */
+  pub fn pop_min(&mut self) -> Option<R> {
+    if self.data.is_empty() {
+      return None;
+    }
+
+    let last = self.data.len() - 1;
+    self.data.swap(0, last);
+    let result = self.data.pop();
+
+    if !self.data.is_empty() {
+      self.trickle_down_min(0);
+    }
+
+    result.map(|x| x.item)
+  }
+
+  /** Remove and return the largest item in the queue */
+  pub fn pop_max(&mut self) -> Option<R> {
+    match self.data.len() {
+      0 => None,
+      1 => self.data.pop().map(|x| x.item),
+      _ => {
+        let max_idx = if self.data.len() == 2 || self.data[1].priority > self.data[2].priority { 1 } else { 2 };
+
+        let last = self.data.len() - 1;
+        self.data.swap(max_idx, last);
+        let result = self.data.pop();
+
+        if max_idx < self.data.len() {
+          self.trickle_down_max(max_idx);
+        }
+
+        result.map(|x| x.item)
+      }
+    }
   }
 }
 
@@ -147,6 +674,168 @@ mod tests {
     assert!(queue.take().is_none());
   }
 
+  #[test]
+  fn empty_queue_is_empty() {
+    let queue = PriorityQueue::<usize, usize>::new(100, PriorityMode::MinimizeHead);
+    assert!(queue.is_empty());
+    assert_eq!(queue.len(), 0);
+    assert!(queue.peek().is_none());
+  }
+
+  #[test]
+  fn peek_does_not_remove() {
+    let mut queue = PriorityQueue::new(100, PriorityMode::MaximizeHead);
+    queue.insert(1, 10);
+    queue.insert(2, 20);
+
+    assert_eq!(*queue.peek().unwrap(), 2);
+    assert_eq!(queue.len(), 2);
+    assert!(!queue.is_empty());
+    assert_eq!(*queue.peek().unwrap(), 2);
+    assert_eq!(queue.take().unwrap(), 2);
+  }
+
+  #[test]
+  fn clear_empties_the_queue() {
+    let mut queue = PriorityQueue::new(100, PriorityMode::MaximizeHead);
+    queue.insert(1, 10);
+    queue.insert(2, 20);
+
+    queue.clear();
+
+    assert!(queue.is_empty());
+    assert_eq!(queue.len(), 0);
+    assert!(queue.take().is_none());
+  }
+
+  #[test]
+  fn reserve_grows_capacity() {
+    let mut queue = PriorityQueue::<usize, usize>::new(0, PriorityMode::MinimizeHead);
+    queue.reserve(64);
+    assert!(queue.capacity() >= 64);
+  }
+
+  #[test]
+  fn into_sorted_vec_ascending_for_minimize() {
+    let mut queue = PriorityQueue::new(100, PriorityMode::MinimizeHead);
+    queue.insert(3, 30);
+    queue.insert(1, 10);
+    queue.insert(2, 20);
+
+    assert_eq!(queue.into_sorted_vec(), vec![1, 2, 3]);
+  }
+
+  #[test]
+  fn replace_returns_old_head_and_sifts_in_new_item() {
+    let mut queue = PriorityQueue::new(100, PriorityMode::MaximizeHead);
+    queue.insert(1, 10);
+    queue.insert(2, 20);
+    queue.insert(3, 30);
+
+    assert_eq!(queue.replace(0, 40).unwrap(), 3);
+    assert_eq!(queue.take().unwrap(), 0);
+    assert_eq!(queue.take().unwrap(), 2);
+    assert_eq!(queue.take().unwrap(), 1);
+  }
+
+  #[test]
+  fn replace_on_empty_queue_just_inserts() {
+    let mut queue = PriorityQueue::<usize, usize>::new(100, PriorityMode::MaximizeHead);
+    assert!(queue.replace(1, 10).is_none());
+    assert_eq!(queue.take().unwrap(), 1);
+  }
+
+  #[test]
+  fn push_pop_returns_newcomer_if_it_does_not_beat_the_head() {
+    let mut queue = PriorityQueue::new(100, PriorityMode::MaximizeHead);
+    queue.insert(10, 10);
+    queue.insert(20, 20);
+
+    assert_eq!(queue.push_pop(5, 5), 5);
+    assert_eq!(queue.len(), 2);
+    assert_eq!(queue.take().unwrap(), 20);
+  }
+
+  #[test]
+  fn push_pop_swaps_in_a_better_item() {
+    let mut queue = PriorityQueue::new(100, PriorityMode::MaximizeHead);
+    queue.insert(10, 10);
+    queue.insert(20, 20);
+
+    assert_eq!(queue.push_pop(30, 30), 20);
+    assert_eq!(queue.len(), 2);
+    assert_eq!(queue.take().unwrap(), 30);
+    assert_eq!(queue.take().unwrap(), 10);
+  }
+
+  #[test]
+  fn bounded_queue_keeps_the_k_largest() {
+    let mut queue = PriorityQueue::with_capacity_limit(3, PriorityMode::MaximizeHead);
+
+    for i in 0..10 {
+      queue.insert(i, i);
+    }
+
+    assert_eq!(queue.len(), 3);
+    assert_eq!(queue.into_sorted_vec(), vec![9, 8, 7]);
+  }
+
+  #[test]
+  fn bounded_queue_keeps_the_k_smallest() {
+    let mut queue = PriorityQueue::with_capacity_limit(3, PriorityMode::MinimizeHead);
+
+    for i in (0..10).rev() {
+      queue.insert(i, i);
+    }
+
+    assert_eq!(queue.len(), 3);
+    assert_eq!(queue.into_sorted_vec(), vec![0, 1, 2]);
+  }
+
+  #[test]
+  fn bounded_queue_of_zero_keeps_nothing() {
+    let mut queue = PriorityQueue::with_capacity_limit(0, PriorityMode::MaximizeHead);
+    queue.insert(1, 10);
+    assert!(queue.is_empty());
+  }
+
+  #[test]
+  fn bounded_queue_handle_table_stays_bounded_over_a_long_stream() {
+    let k = 10;
+    let mut queue = PriorityQueue::with_capacity_limit(k, PriorityMode::MaximizeHead);
+
+    for i in 0..1_000_000 {
+      queue.insert(i, i);
+    }
+
+    assert_eq!(queue.len(), k);
+    assert!(queue.handle_to_index.len() <= k);
+  }
+
+  #[test]
+  fn large_bounded_queue_keeps_the_k_largest() {
+    let size = 100000;
+    let k = 100;
+    let mut queue = PriorityQueue::with_capacity_limit(k, PriorityMode::MaximizeHead);
+
+    for i in 0..size {
+      queue.insert(i, i);
+    }
+
+    let expected: Vec<usize> = (size - k..size).rev().collect();
+    assert_eq!(queue.into_sorted_vec(), expected);
+  }
+
+  #[test]
+  fn into_sorted_vec_descending_for_maximize() {
+    let mut queue = PriorityQueue::new(100, PriorityMode::MaximizeHead);
+    queue.insert(3, 30);
+    queue.insert(1, 10);
+    queue.insert(2, 20);
+
+    assert_eq!(queue.into_sorted_vec(), vec![3, 2, 1]);
+  }
+
   #[test]
   fn simple_queue_maximize() {
     let mut queue = PriorityQueue::new(100, PriorityMode::MaximizeHead);
@@ -217,6 +906,46 @@ mod tests {
     }
   }
 
+  #[test]
+  fn from_vec_maximize() {
+    let items = vec![(1, 10), (2, 20), (3, 30)];
+    let mut queue = PriorityQueue::from_vec(items, PriorityMode::MaximizeHead);
+    assert_eq!(queue.take().unwrap(), 3);
+    assert_eq!(queue.take().unwrap(), 2);
+    assert_eq!(queue.take().unwrap(), 1);
+  }
+
+  #[test]
+  fn from_vec_minimize() {
+    let items = vec![(1, 10), (2, 20), (3, 30)];
+    let mut queue = PriorityQueue::from_vec(items, PriorityMode::MinimizeHead);
+    assert_eq!(queue.take().unwrap(), 1);
+    assert_eq!(queue.take().unwrap(), 2);
+    assert_eq!(queue.take().unwrap(), 3);
+  }
+
+  #[test]
+  fn large_from_vec_maximize() {
+    let size = 100000;
+    let items: Vec<(usize, usize)> = (0..size).map(|i| (i, i)).collect();
+    let mut queue = PriorityQueue::from_vec(items, PriorityMode::MaximizeHead);
+
+    for i in 0..size {
+      assert_eq!(queue.take().unwrap(), size - i - 1);
+    }
+  }
+
+  #[test]
+  fn large_from_vec_minimize() {
+    let size = 100000;
+    let items: Vec<(usize, usize)> = (0..size).map(|i| (i, i)).collect();
+    let mut queue = PriorityQueue::from_vec(items, PriorityMode::MinimizeHead);
+
+    for i in 0..size {
+      assert_eq!(queue.take().unwrap(), i);
+    }
+  }
+
   #[test]
   fn test_random_maximize() {
     let mut queue = PriorityQueue::new(100, PriorityMode::MaximizeHead);
@@ -235,4 +964,182 @@ mod tests {
       head = nval;
     }
   }
+
+  #[test]
+  fn update_priority_moves_an_item_towards_the_root() {
+    let mut queue = PriorityQueue::new(100, PriorityMode::MinimizeHead);
+    queue.insert(1, 10);
+    let handle = queue.insert(2, 20);
+    queue.insert(3, 30);
+
+    assert!(queue.update_priority(handle, 5));
+    assert_eq!(queue.take().unwrap(), 2);
+    assert_eq!(queue.take().unwrap(), 1);
+    assert_eq!(queue.take().unwrap(), 3);
+  }
+
+  #[test]
+  fn update_priority_moves_an_item_away_from_the_root() {
+    let mut queue = PriorityQueue::new(100, PriorityMode::MinimizeHead);
+    let handle = queue.insert(1, 10);
+    queue.insert(2, 20);
+    queue.insert(3, 30);
+
+    assert!(queue.update_priority(handle, 100));
+    assert_eq!(queue.take().unwrap(), 2);
+    assert_eq!(queue.take().unwrap(), 3);
+    assert_eq!(queue.take().unwrap(), 1);
+  }
+
+  #[test]
+  fn update_priority_on_a_stale_handle_returns_false() {
+    let mut queue = PriorityQueue::new(100, PriorityMode::MinimizeHead);
+    let handle = queue.insert(1, 10);
+    queue.take();
+
+    assert!(!queue.update_priority(handle, 5));
+  }
+
+  #[test]
+  fn remove_deletes_an_arbitrary_item_by_handle() {
+    let mut queue = PriorityQueue::new(100, PriorityMode::MinimizeHead);
+    queue.insert(1, 10);
+    let handle = queue.insert(2, 20);
+    queue.insert(3, 30);
+
+    assert_eq!(queue.remove(handle).unwrap(), 2);
+    assert_eq!(queue.len(), 2);
+    assert_eq!(queue.into_sorted_vec(), vec![1, 3]);
+  }
+
+  #[test]
+  fn remove_on_a_stale_handle_returns_none() {
+    let mut queue = PriorityQueue::new(100, PriorityMode::MinimizeHead);
+    let handle = queue.insert(1, 10);
+
+    assert!(queue.remove(handle).is_some());
+    assert!(queue.remove(handle).is_none());
+  }
+
+  #[test]
+  fn dijkstra_style_relax_keeps_the_cheapest_route_at_the_head() {
+    let mut queue = PriorityQueue::new(100, PriorityMode::MinimizeHead);
+    let a = queue.insert("a", 100);
+    let b = queue.insert("b", 50);
+    queue.insert("c", 10);
+
+    // Relaxing a couple of edges should re-establish the heap property without duplicate entries
+    queue.update_priority(a, 5);
+    queue.update_priority(b, 200);
+
+    assert_eq!(queue.len(), 3);
+    assert_eq!(queue.take().unwrap(), "a");
+    assert_eq!(queue.take().unwrap(), "c");
+    assert_eq!(queue.take().unwrap(), "b");
+  }
+
+  #[test]
+  fn large_random_update_priority_keeps_the_heap_valid() {
+    // The item is the original slot index so that, after every priority is replaced with a fresh
+    // random value, we can still tell whether the items come back out in priority order
+    let mut queue = PriorityQueue::new(100, PriorityMode::MinimizeHead);
+    let size = 10000;
+    let mut handles = Vec::with_capacity(size);
+    let mut priorities = vec![0usize; size];
+
+    for i in 0..size {
+      let rval: usize = random();
+      handles.push(queue.insert(i, rval));
+    }
+
+    for (i, handle) in handles.into_iter().enumerate() {
+      let rval: usize = random();
+      priorities[i] = rval;
+      queue.update_priority(handle, rval);
+    }
+
+    let mut head = queue.take().unwrap();
+
+    for _ in 0..(size - 1) {
+      let nval = queue.take().unwrap();
+      assert!(priorities[head] <= priorities[nval]);
+      head = nval;
+    }
+  }
+
+  #[test]
+  fn empty_double_ended_queue_gives_none() {
+    let mut queue = DoubleEndedPriorityQueue::<usize, usize>::new(100);
+    assert!(queue.peek_min().is_none());
+    assert!(queue.peek_max().is_none());
+    assert!(queue.pop_min().is_none());
+    assert!(queue.pop_max().is_none());
+  }
+
+  #[test]
+  fn double_ended_queue_exposes_both_extremes() {
+    let mut queue = DoubleEndedPriorityQueue::new(100);
+    queue.insert(3, 30);
+    queue.insert(1, 10);
+    queue.insert(4, 40);
+    queue.insert(2, 20);
+
+    assert_eq!(*queue.peek_min().unwrap(), 1);
+    assert_eq!(*queue.peek_max().unwrap(), 4);
+  }
+
+  #[test]
+  fn double_ended_queue_pop_min_in_order() {
+    let mut queue = DoubleEndedPriorityQueue::new(100);
+    for i in [5, 3, 8, 1, 9, 2, 7] {
+      queue.insert(i, i);
+    }
+
+    let mut result = Vec::new();
+    while let Some(item) = queue.pop_min() {
+      result.push(item);
+    }
+
+    assert_eq!(result, vec![1, 2, 3, 5, 7, 8, 9]);
+  }
+
+  #[test]
+  fn double_ended_queue_pop_max_in_order() {
+    let mut queue = DoubleEndedPriorityQueue::new(100);
+    for i in [5, 3, 8, 1, 9, 2, 7] {
+      queue.insert(i, i);
+    }
+
+    let mut result = Vec::new();
+    while let Some(item) = queue.pop_max() {
+      result.push(item);
+    }
+
+    assert_eq!(result, vec![9, 8, 7, 5, 3, 2, 1]);
+  }
+
+  #[test]
+  fn large_double_ended_queue_random_pop_min_and_max_meet_in_the_middle() {
+    let size = 100000;
+    let mut queue = DoubleEndedPriorityQueue::new(size);
+
+    for _ in 0..size {
+      let rval: usize = random();
+      queue.insert(rval, rval);
+    }
+
+    let mut min = queue.pop_min().unwrap();
+    let mut max = queue.pop_max().unwrap();
+    assert!(min <= max);
+
+    for _ in 0..((size - 2) / 2) {
+      let next_min = queue.pop_min().unwrap();
+      let next_max = queue.pop_max().unwrap();
+      assert!(next_min >= min);
+      assert!(next_max <= max);
+      assert!(next_min <= next_max);
+      min = next_min;
+      max = next_max;
+    }
+  }
 }